@@ -1,69 +1,145 @@
-use csv::Writer;
+use csv::WriterBuilder;
 use fake::faker;
 use fake::Fake;
+use memmap2::MmapMut;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{self, Write};
 use std::time::Instant;
 
 const FILE_PATH: &str = "large_file.csv";
 const ROWS: usize = 25_000_000; // Approx. 10GB
-const CHUNK_SIZE: usize = 1000; // Rows per batch
-const LOG_INTERVAL: usize = 250 * 1024 * 1024; // Log every 250MB
-const ESTIMATED_ROW_SIZE: usize = 400; // Approximate row size in bytes
-
-fn generate_fake_data() -> Vec<String> {
-    let sanitize = |s: String| s.replace("\n", " ").replace("\r", " "); // Remove newlines
-
-    vec![
-        sanitize(fake::faker::name::en::Name().fake()),
-        sanitize(fake::faker::internet::en::SafeEmail().fake()),
-        sanitize(fake::uuid::UUIDv4.fake()),
-        sanitize(fake::faker::phone_number::en::PhoneNumber().fake()),
-        sanitize(fake::faker::company::en::CompanyName().fake()),
-        sanitize(faker::company::en::Buzzword().fake()),
-        sanitize(fake::faker::lorem::en::Sentence(5..10).fake()),
-        sanitize(fake::faker::time::en::Date().fake()),
+
+// Keep this in lockstep with generate_fake_data's field count: a mismatch
+// here silently shifts every downstream column relative to the header.
+const HEADER: [&str; 8] = [
+    "Name", "Email", "UUID", "Phone", "Company", "Buzzword", "Sentence", "Date",
+];
+
+fn generate_fake_data(rng: &mut StdRng) -> [String; 8] {
+    let sanitize = |s: String| s.replace('\n', " ").replace('\r', " "); // Remove newlines
+
+    [
+        sanitize(fake::faker::name::en::Name().fake_with_rng(rng)),
+        sanitize(fake::faker::internet::en::SafeEmail().fake_with_rng(rng)),
+        sanitize(fake::uuid::UUIDv4.fake_with_rng(rng)),
+        sanitize(fake::faker::phone_number::en::PhoneNumber().fake_with_rng(rng)),
+        sanitize(fake::faker::company::en::CompanyName().fake_with_rng(rng)),
+        sanitize(faker::company::en::Buzzword().fake_with_rng(rng)),
+        sanitize(fake::faker::lorem::en::Sentence(5..10).fake_with_rng(rng)),
+        sanitize(fake::faker::time::en::Date().fake_with_rng(rng)),
     ]
 }
 
+/// A `Write` sink that only tallies how many bytes would have been
+/// written, so a chunk's rendered size can be measured without holding the
+/// rendered bytes in memory.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Renders `row_count` fake rows (no header) through `writer`, seeded so the
+/// same `seed` always reproduces the same rows regardless of whether it is
+/// called to measure a chunk's length or to fill its final mmap region.
+fn write_chunk<W: Write>(writer: W, row_count: usize, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut writer = WriterBuilder::new().has_headers(false).from_writer(writer);
+
+    for _ in 0..row_count {
+        let row = generate_fake_data(&mut rng);
+        writer.write_record(&row).expect("Failed to write row");
+    }
+
+    writer.flush().expect("Failed to flush chunk");
+}
+
+fn chunk_byte_len(row_count: usize, seed: u64) -> usize {
+    let mut counter = ByteCounter(0);
+    write_chunk(&mut counter, row_count, seed);
+    counter.0
+}
+
+fn render_header() -> Vec<u8> {
+    let mut writer = WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    writer.write_record(HEADER).expect("Failed to write header");
+    writer.flush().expect("Failed to flush header");
+    writer.into_inner().expect("Failed to get header buffer")
+}
+
 fn main() {
     let start_time = Instant::now();
+
+    let thread_count = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let rows_per_thread = ROWS / thread_count;
+    let extra_rows = ROWS % thread_count;
+
+    println!(
+        "Generating {} rows across {} threads...",
+        ROWS, thread_count
+    );
+
+    let worker_rows: Vec<usize> = (0..thread_count)
+        .map(|worker| rows_per_thread + if worker < extra_rows { 1 } else { 0 })
+        .collect();
+
+    // First pass: measure each worker's exact CSV byte length without
+    // retaining the rendered rows, so the file can be pre-sized without
+    // ever holding the whole ~10GB dataset on the heap at once.
+    let chunk_lens: Vec<usize> = worker_rows
+        .par_iter()
+        .enumerate()
+        .map(|(worker, &rows)| chunk_byte_len(rows, worker as u64))
+        .collect();
+
+    let header = render_header();
+    let total_len = header.len() + chunk_lens.iter().sum::<usize>();
+
     let file = File::create(FILE_PATH).expect("Failed to create file");
-    let mut writer = Writer::from_writer(BufWriter::new(file));
-
-    // Write header
-    writer
-        .write_record(&[
-            "Name", "Email", "City", "UUID", "Phone", "Company", "Job", "Sentence", "Date", "Bool",
-        ])
-        .expect("Failed to write header");
-
-    let mut total_bytes_written: usize = 0;
-
-    for batch in 0..(ROWS / CHUNK_SIZE) {
-        let data: Vec<Vec<String>> = (0..CHUNK_SIZE).map(|_| generate_fake_data()).collect();
-
-        for row in data {
-            writer.write_record(&row).expect("Failed to write row");
-        }
-
-        // Estimate bytes written
-        total_bytes_written += CHUNK_SIZE * ESTIMATED_ROW_SIZE;
-
-        if total_bytes_written >= LOG_INTERVAL {
-            println!(
-                "Batch {}: Generated {:.2} MB...",
-                batch + 1,
-                (total_bytes_written as f64) / (1024.0 * 1024.0)
-            );
-            total_bytes_written = 0; // Reset counter
-        }
+    file.set_len(total_len as u64)
+        .expect("Failed to pre-size output file");
+    let mut mmap = unsafe { MmapMut::map_mut(&file).expect("Failed to mmap output file") };
+
+    let (header_region, mut rest) = mmap.split_at_mut(header.len());
+    header_region.copy_from_slice(&header);
+
+    // Each region is a disjoint slice of the mapping, so filling them in
+    // parallel needs no locking on the hot path.
+    let mut regions = Vec::with_capacity(chunk_lens.len());
+    for &len in &chunk_lens {
+        let (region, remainder) = rest.split_at_mut(len);
+        regions.push(region);
+        rest = remainder;
     }
 
-    writer.flush().expect("Failed to flush data");
+    // Second pass: regenerate the same seeded rows, this time writing
+    // straight into each worker's mmap region instead of a heap buffer.
+    regions
+        .into_par_iter()
+        .zip(worker_rows.into_par_iter())
+        .enumerate()
+        .for_each(|(worker, (region, rows))| write_chunk(region, rows, worker as u64));
+
+    mmap.flush().expect("Failed to flush mmap to disk");
 
     println!(
-        "10GB CSV generated in {:.2} seconds",
-        start_time.elapsed().as_secs_f64()
+        "{} generated in {:.2} seconds across {} threads",
+        FILE_PATH,
+        start_time.elapsed().as_secs_f64(),
+        thread_count
     );
 }
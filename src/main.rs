@@ -1,16 +1,10 @@
-use serde::{Deserialize, Serialize};
-use std::{error::Error, fmt};
-
-// --- Data Structure ---
-// This struct will be our intermediary representation.
-// We derive Serialize and Deserialize for it.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-struct Record {
-    id: u32,
-    name: String,
-    value: f64,
-    active: bool,
-}
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt,
+    path::Path,
+};
 
 // --- Format Enum ---
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,6 +13,11 @@ enum Format {
     Yaml,
     Csv,
     Toml,
+    Bson,
+    Tsv,
+    Ini,
+    Ron,
+    Json5,
 }
 
 impl fmt::Display for Format {
@@ -27,6 +26,124 @@ impl fmt::Display for Format {
     }
 }
 
+impl Format {
+    /// Infers the format of `input` by trial deserialization, trying each
+    /// candidate format in turn and returning the first that parses.
+    ///
+    /// Order matters: JSON is a strict subset of YAML, so it must be tried
+    /// before YAML or every JSON document would be misclassified as YAML.
+    /// CSV/TSV can't be told apart from other formats by trial parsing since
+    /// a line of delimited scalars happens to parse as YAML too, so they are
+    /// recognized separately by a header-row/delimiter check before any
+    /// trial parsing happens. YAML is tried last because it's the most
+    /// permissive of the text-based formats here (almost any scalar or
+    /// indented block parses as *something*), so it would shadow the
+    /// stricter formats if tried earlier.
+    ///
+    /// The TOML/BSON/INI probes below deliberately try a bare `toml::Value`/
+    /// generic `Value` rather than a `records`-wrapped struct: that's the
+    /// same shape `deserialize_from_string` now accepts for these formats
+    /// (see `wrap_for_table`/`unwrap_from_table`), so detection and
+    /// deserialization agree on what counts as a valid document.
+    fn detect(input: &str) -> Option<Format> {
+        if input.trim().is_empty() {
+            return None;
+        }
+
+        if looks_like_delimited(input, b',') {
+            return Some(Format::Csv);
+        }
+        if looks_like_delimited(input, b'\t') {
+            return Some(Format::Tsv);
+        }
+        if looks_like_bson_hex(input) {
+            return Some(Format::Bson);
+        }
+        if serde_json::from_str::<Value>(input).is_ok() {
+            return Some(Format::Json);
+        }
+        if json5::from_str::<Value>(input).is_ok() {
+            return Some(Format::Json5);
+        }
+        if toml::from_str::<toml::Value>(input).is_ok() {
+            return Some(Format::Toml);
+        }
+        if ron::from_str::<Value>(input).is_ok() {
+            return Some(Format::Ron);
+        }
+        if serde_ini::from_str::<Value>(input).is_ok() {
+            return Some(Format::Ini);
+        }
+        if serde_yaml::from_str::<Value>(input).is_ok() {
+            return Some(Format::Yaml);
+        }
+
+        None
+    }
+
+    /// Maps a file's extension to its `Format`, the way the CLI picks
+    /// formats for `-o output.<ext>` without the caller spelling it out.
+    fn from_path(path: &Path) -> Result<Format, ConversionError> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .ok_or_else(|| ConversionError::UnknownExtension(path.display().to_string()))?;
+
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "yml" | "yaml" => Ok(Format::Yaml),
+            "csv" => Ok(Format::Csv),
+            "toml" => Ok(Format::Toml),
+            "bson" => Ok(Format::Bson),
+            "tsv" => Ok(Format::Tsv),
+            "ini" => Ok(Format::Ini),
+            "ron" => Ok(Format::Ron),
+            "json5" => Ok(Format::Json5),
+            other => Err(ConversionError::UnknownExtension(other.to_string())),
+        }
+    }
+}
+
+/// Recognizes CSV/TSV by shape rather than by parsing: a header row followed
+/// by at least one data row, all sharing the same delimited column count.
+fn looks_like_delimited(input: &str, delimiter: u8) -> bool {
+    let delimiter = delimiter as char;
+    let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+
+    let Some(header) = lines.next() else {
+        return false;
+    };
+    let column_count = header.split(delimiter).count();
+    if column_count < 2 {
+        return false;
+    }
+
+    let mut saw_data_row = false;
+    for line in lines {
+        if line.split(delimiter).count() != column_count {
+            return false;
+        }
+        saw_data_row = true;
+    }
+
+    saw_data_row
+}
+
+/// Recognizes the hex-encoded BSON documents this converter produces: plain
+/// hex text (so it doesn't collide with other formats) that decodes to a
+/// well-formed BSON document.
+fn looks_like_bson_hex(input: &str) -> bool {
+    let trimmed = input.trim();
+    if trimmed.is_empty() || !trimmed.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return false;
+    }
+
+    let Ok(bytes) = hex::decode(trimmed) else {
+        return false;
+    };
+
+    bson::Document::from_reader(&mut std::io::Cursor::new(bytes)).is_ok()
+}
 
 // --- Custom Error Type ---
 #[derive(Debug, thiserror::Error)]
@@ -45,102 +162,474 @@ enum ConversionError {
     Utf8(#[from] std::string::FromUtf8Error),
     #[error("IO Error: {0}")] // Needed for CSV writer-to-memory
     Io(#[from] std::io::Error),
+    #[error("BSON Serialization Error: {0}")]
+    BsonSer(#[from] bson::ser::Error),
+    #[error("BSON Deserialization Error: {0}")]
+    BsonDe(#[from] bson::de::Error),
+    #[error("Hex Decode Error: {0}")]
+    Hex(#[from] hex::FromHexError),
+    #[error("INI Deserialization Error: {0}")]
+    IniDe(#[from] serde_ini::de::Error),
+    #[error("INI Serialization Error: {0}")]
+    IniSer(#[from] serde_ini::ser::Error),
+    #[error("RON Deserialization Error: {0}")]
+    RonDe(#[from] ron::error::SpannedError),
+    #[error("RON Serialization Error: {0}")]
+    RonSer(#[from] ron::Error),
+    #[error("JSON5 Error: {0}")]
+    Json5(#[from] json5::Error),
+    #[error("Error at `{path}`: {source}")]
+    PathError {
+        path: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
     #[error("Cannot represent this data structure as {0}")]
     UnsupportedRepresentation(Format),
     #[error("Input data for {0} format is empty")]
     EmptyInput(Format),
+    #[error("Could not detect the input format")]
+    UndetectableFormat,
+    #[error("Unrecognized file extension: {0}")]
+    UnknownExtension(String),
+    #[error("CSV column `{0}` conflicts with the document shape implied by the other columns")]
+    CsvColumnConflict(String),
 }
 
-
 // --- Conversion Logic ---
+//
+// The intermediary is an untyped `serde_json::Value` rather than a fixed
+// struct, so arbitrary/heterogeneous/nested documents round-trip through
+// JSON<->YAML<->TOML without a schema. CSV/TSV are the exception: they are
+// inherently flat, so their arms below flatten/re-nest through dotted
+// column names instead (see read_delimited/write_delimited).
 
 // Helper to deserialize from a string based on format
-fn deserialize_from_string(
-    input: &str,
-    format: Format,
-) -> Result<Vec<Record>, ConversionError> {
+fn deserialize_from_string(input: &str, format: Format) -> Result<Value, ConversionError> {
     if input.trim().is_empty() {
         return Err(ConversionError::EmptyInput(format));
     }
 
     match format {
         Format::Json => {
-            let records: Vec<Record> = serde_json::from_str(input)?;
-            Ok(records)
+            let mut deserializer = serde_json::Deserializer::from_str(input);
+            let value: Value =
+                serde_path_to_error::deserialize(&mut deserializer).map_err(path_error)?;
+            Ok(value)
         }
         Format::Yaml => {
-            let records: Vec<Record> = serde_yaml::from_str(input)?;
-            Ok(records)
-        }
-        Format::Csv => {
-            let mut reader = csv::ReaderBuilder::new()
-                .has_headers(true) // Assume CSV has headers matching struct fields
-                .from_reader(input.as_bytes());
-            let mut records = Vec::new();
-            for result in reader.deserialize() {
-                let record: Record = result?;
-                records.push(record);
-            }
-            Ok(records)
+            let deserializer = serde_yaml::Deserializer::from_str(input);
+            let value: Value =
+                serde_path_to_error::deserialize(deserializer).map_err(path_error)?;
+            Ok(value)
+        }
+        Format::Csv => read_delimited(input, b','),
+        Format::Tsv => read_delimited(input, b'\t'),
+        Format::Bson => {
+            // BSON is a binary format, so it travels through our string
+            // pipeline hex-encoded rather than as raw bytes.
+            let bytes = hex::decode(input.trim())?;
+            let document = bson::Document::from_reader(&mut bytes.as_slice())?;
+            let deserializer = bson::Deserializer::new(bson::Bson::Document(document));
+            let value: Value =
+                serde_path_to_error::deserialize(deserializer).map_err(path_error)?;
+            Ok(unwrap_from_table(value))
+        }
+        Format::Ini => {
+            let mut deserializer = serde_ini::Deserializer::from_str(input);
+            let value: Value =
+                serde_path_to_error::deserialize(&mut deserializer).map_err(path_error)?;
+            Ok(unwrap_from_table(value))
+        }
+        Format::Ron => {
+            let mut deserializer = ron::Deserializer::from_str(input)?;
+            let value: Value =
+                serde_path_to_error::deserialize(&mut deserializer).map_err(path_error)?;
+            Ok(value)
+        }
+        Format::Json5 => {
+            let mut deserializer = json5::Deserializer::from_str(input)?;
+            let value: Value =
+                serde_path_to_error::deserialize(&mut deserializer).map_err(path_error)?;
+            Ok(value)
         }
         Format::Toml => {
-            // TOML often represents a single structure. If we want a list,
-            // it's typically represented as an array of tables.
-            // We might need a wrapper struct if the TOML isn't directly an array.
-            // Let's assume the TOML *is* an array of tables representing Vec<Record>.
-            // Or, more commonly, it's under a specific key. Let's use a wrapper.
-            #[derive(Deserialize)]
-            struct TomlWrapper {
-                records: Vec<Record>,
+            // TOML/BSON/INI all require a top-level table; wrap_for_table/
+            // unwrap_from_table let an array-of-records Value (the shape
+            // CSV/JSON use everywhere else here) and a genuine external map
+            // both round-trip instead of only this tool's own output.
+            let deserializer = toml::de::Deserializer::new(input);
+            let value: Value =
+                serde_path_to_error::deserialize(deserializer).map_err(path_error)?;
+            Ok(unwrap_from_table(value))
+        }
+    }
+}
+
+/// Turns a `serde_path_to_error::Error` into a `ConversionError::PathError`,
+/// preserving the dotted path to the field that failed (e.g.
+/// `records[2].value`) instead of only the backend crate's bare message.
+fn path_error<E>(err: serde_path_to_error::Error<E>) -> ConversionError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    ConversionError::PathError {
+        path: err.path().to_string(),
+        source: Box::new(err.into_inner()),
+    }
+}
+
+/// TOML/BSON/INI all require a top-level map/table, but our `Value`
+/// intermediary is often an array (the array-of-records shape CSV/JSON use
+/// everywhere else in this converter). Wrap an array under a `records` key
+/// so it has a legal home in these formats; leave maps untouched so a
+/// genuine external TOML/BSON/INI document (already shaped as a map, with
+/// no `records` key of its own) serializes straight through too, instead of
+/// only this tool's own previously-wrapped output.
+fn wrap_for_table(value: &Value) -> Value {
+    match value {
+        Value::Array(_) => {
+            let mut wrapper = serde_json::Map::new();
+            wrapper.insert("records".to_string(), value.clone());
+            Value::Object(wrapper)
+        }
+        other => other.clone(),
+    }
+}
+
+/// Inverse of `wrap_for_table`: unwraps the `records` key only if that's the
+/// document's entire shape (a single-key map named `records`), so a document
+/// that's already a plain map of its own fields comes back untouched.
+fn unwrap_from_table(mut value: Value) -> Value {
+    if let Value::Object(map) = &mut value {
+        if map.len() == 1 && map.contains_key("records") {
+            return map.remove("records").expect("checked above");
+        }
+    }
+    value
+}
+
+// --- CSV/TSV flattening ---
+//
+// The `csv` crate can only serialize flat records, so the delimited arms of
+// deserialize_from_string/serialize_to_string funnel through here instead of
+// going through Value's normal (de)serializer. Nested objects/arrays become
+// dotted columns (`child.0.name`) and are re-nested on the way back in.
+
+/// Walks `value`, emitting one entry per scalar leaf keyed by its dotted
+/// path from `prefix` (e.g. `child.0.name`).
+fn flatten_value(value: &Value, prefix: &str) -> BTreeMap<String, String> {
+    let mut flattened = BTreeMap::new();
+
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flattened.extend(flatten_value(child, &path));
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                let path = if prefix.is_empty() {
+                    index.to_string()
+                } else {
+                    format!("{prefix}.{index}")
+                };
+                flattened.extend(flatten_value(child, &path));
+            }
+        }
+        Value::Null => {
+            flattened.insert(prefix.to_string(), String::new());
+        }
+        Value::Bool(b) => {
+            flattened.insert(prefix.to_string(), b.to_string());
+        }
+        Value::Number(n) => {
+            flattened.insert(prefix.to_string(), n.to_string());
+        }
+        Value::String(s) => {
+            flattened.insert(prefix.to_string(), s.clone());
+        }
+    }
+
+    flattened
+}
+
+/// Inverse of `flatten_value` for a single row: walks `path`'s dotted
+/// segments into `container`, creating an object or array at each level
+/// depending on whether the next segment looks like an array index.
+///
+/// `header` is only the original column name, kept around to name the
+/// conflict if `path` disagrees with the shape another column already
+/// built (e.g. a purely-numeric top-level header like `2020` expecting an
+/// array where the record is an object, or `a`/`a.b` both claiming key `a`
+/// at incompatible types) — these are rejected as a `ConversionError`
+/// rather than tripping an `.expect()` on untrusted input.
+fn insert_dotted(
+    container: &mut Value,
+    path: &[&str],
+    raw: &str,
+    header: &str,
+) -> Result<(), ConversionError> {
+    let key = path[0];
+    let rest = &path[1..];
+
+    if rest.is_empty() {
+        return set_child(container, key, parse_scalar(raw), header);
+    }
+
+    ensure_child(container, key, rest[0].parse::<usize>().is_ok(), header)?;
+    insert_dotted(child_mut(container, key, header)?, rest, raw, header)
+}
+
+fn conflict(header: &str) -> ConversionError {
+    ConversionError::CsvColumnConflict(header.to_string())
+}
+
+fn set_child(
+    container: &mut Value,
+    key: &str,
+    value: Value,
+    header: &str,
+) -> Result<(), ConversionError> {
+    if let Ok(index) = key.parse::<usize>() {
+        let array = container.as_array_mut().ok_or_else(|| conflict(header))?;
+        if array.len() <= index {
+            array.resize(index + 1, Value::Null);
+        }
+        if matches!(array[index], Value::Object(_) | Value::Array(_)) {
+            return Err(conflict(header));
+        }
+        array[index] = value;
+    } else {
+        let object = container.as_object_mut().ok_or_else(|| conflict(header))?;
+        if matches!(
+            object.get(key),
+            Some(Value::Object(_)) | Some(Value::Array(_))
+        ) {
+            return Err(conflict(header));
+        }
+        object.insert(key.to_string(), value);
+    }
+    Ok(())
+}
+
+fn ensure_child(
+    container: &mut Value,
+    key: &str,
+    child_is_array: bool,
+    header: &str,
+) -> Result<(), ConversionError> {
+    let placeholder = || {
+        if child_is_array {
+            Value::Array(Vec::new())
+        } else {
+            Value::Object(serde_json::Map::new())
+        }
+    };
+
+    if let Ok(index) = key.parse::<usize>() {
+        let array = container.as_array_mut().ok_or_else(|| conflict(header))?;
+        if array.len() <= index {
+            array.resize(index + 1, Value::Null);
+        }
+        match &array[index] {
+            Value::Null => array[index] = placeholder(),
+            Value::Object(_) if !child_is_array => {}
+            Value::Array(_) if child_is_array => {}
+            _ => return Err(conflict(header)),
+        }
+    } else {
+        let object = container.as_object_mut().ok_or_else(|| conflict(header))?;
+        match object.get(key) {
+            None => {
+                object.insert(key.to_string(), placeholder());
             }
-            let wrapper: TomlWrapper = toml::from_str(input)?;
-            Ok(wrapper.records)
-            // // If the TOML was *just* the array of tables directly:
-            // let records: Vec<Record> = toml::from_str(input)?;
-            // Ok(records)
+            Some(Value::Object(_)) if !child_is_array => {}
+            Some(Value::Array(_)) if child_is_array => {}
+            _ => return Err(conflict(header)),
         }
     }
+    Ok(())
+}
+
+fn child_mut<'a>(
+    container: &'a mut Value,
+    key: &str,
+    header: &str,
+) -> Result<&'a mut Value, ConversionError> {
+    if let Ok(index) = key.parse::<usize>() {
+        container
+            .as_array_mut()
+            .ok_or_else(|| conflict(header))?
+            .get_mut(index)
+            .ok_or_else(|| conflict(header))
+    } else {
+        container
+            .as_object_mut()
+            .ok_or_else(|| conflict(header))?
+            .get_mut(key)
+            .ok_or_else(|| conflict(header))
+    }
+}
+
+/// Parses a CSV cell back into the scalar it most likely came from, falling
+/// back to a plain string (`flatten_value`'s `Value::to_string`/`Display`
+/// output round-trips through this for bools and numbers).
+///
+/// This is a heuristic, not a type-safe inverse: a string cell that happens
+/// to look like a bool or number (`"true"`, `"007"`) is indistinguishable
+/// from the real thing once it's been through CSV, so it comes back as that
+/// bool/number rather than the original string. Callers that need to tell
+/// `"007"` from `7` should keep a separate type-hint column rather than
+/// relying on this inference.
+fn parse_scalar(raw: &str) -> Value {
+    if let Ok(b) = raw.parse::<bool>() {
+        return Value::Bool(b);
+    }
+    if let Ok(n) = raw.parse::<i64>() {
+        return Value::Number(n.into());
+    }
+    if let Ok(f) = raw.parse::<f64>() {
+        if let Some(n) = serde_json::Number::from_f64(f) {
+            return Value::Number(n);
+        }
+    }
+    Value::String(raw.to_string())
+}
+
+/// Shared CSV/TSV deserializer: `delimiter` is the only difference between
+/// the two formats.
+fn read_delimited(input: &str, delimiter: u8) -> Result<Value, ConversionError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(true)
+        .from_reader(input.as_bytes());
+
+    let headers = reader.headers()?.clone();
+    let mut records = Vec::new();
+    for result in reader.records() {
+        let row = result?;
+        let mut record = Value::Object(serde_json::Map::new());
+        for (header, raw) in headers.iter().zip(row.iter()) {
+            if raw.is_empty() {
+                continue;
+            }
+            let path: Vec<&str> = header.split('.').collect();
+            insert_dotted(&mut record, &path, raw, header)?;
+        }
+        records.push(record);
+    }
+
+    Ok(Value::Array(records))
+}
+
+/// Shared CSV/TSV serializer: flattens each top-level record, unions all
+/// their keys into one header set, and fills missing cells with "".
+fn write_delimited(value: &Value, delimiter: u8) -> Result<String, ConversionError> {
+    let format = if delimiter == b'\t' {
+        Format::Tsv
+    } else {
+        Format::Csv
+    };
+    let records = value
+        .as_array()
+        .ok_or(ConversionError::UnsupportedRepresentation(format))?;
+
+    let flattened: Vec<BTreeMap<String, String>> = records
+        .iter()
+        .map(|record| flatten_value(record, ""))
+        .collect();
+
+    let headers: BTreeSet<&String> = flattened.iter().flat_map(BTreeMap::keys).collect();
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(true)
+        .from_writer(Vec::new());
+    writer.write_record(headers.iter().map(|h| h.as_str()))?;
+    for row in &flattened {
+        writer.write_record(
+            headers
+                .iter()
+                .map(|h| row.get(*h).map_or("", String::as_str)),
+        )?;
+    }
+    writer.flush()?;
+
+    Ok(String::from_utf8(writer.into_inner().unwrap())?)
 }
 
 // Helper to serialize to a string based on format
-fn serialize_to_string(
-    records: &[Record],
-    format: Format,
-) -> Result<String, ConversionError> {
+fn serialize_to_string(value: &Value, format: Format) -> Result<String, ConversionError> {
     match format {
         Format::Json => {
-            let json_string = serde_json::to_string_pretty(records)?;
-            Ok(json_string)
+            let mut buf = Vec::new();
+            let mut serializer = serde_json::Serializer::with_formatter(
+                &mut buf,
+                serde_json::ser::PrettyFormatter::new(),
+            );
+            serde_path_to_error::serialize(value, &mut serializer).map_err(path_error)?;
+            Ok(String::from_utf8(buf)?)
         }
         Format::Yaml => {
-            let yaml_string = serde_yaml::to_string(records)?;
-            Ok(yaml_string)
-        }
-        Format::Csv => {
-            // Write CSV to a Vec<u8> in memory, then convert to String
-            let mut writer = csv::WriterBuilder::new()
-                .has_headers(true) // Write headers based on struct fields
-                .from_writer(Vec::new());
-            for record in records {
-                writer.serialize(record)?;
-            }
-            writer.flush()?; // Ensure all data is written to the buffer
-            let csv_data = writer.into_inner().unwrap();
-            let csv_string = String::from_utf8(csv_data)?;
-            Ok(csv_string)
+            // serde_yaml::Serializer needs an `io::Write` sink, not the
+            // `fmt::Write` a `String` implements, so it's built over a byte
+            // buffer (like the JSON arm above) and decoded afterwards.
+            let mut buf = Vec::new();
+            let mut serializer = serde_yaml::Serializer::new(&mut buf);
+            serde_path_to_error::serialize(value, &mut serializer).map_err(path_error)?;
+            Ok(String::from_utf8(buf)?)
+        }
+        Format::Csv => write_delimited(value, b','),
+        Format::Tsv => write_delimited(value, b'\t'),
+        Format::Bson => {
+            // The `bson` crate only exposes whole-value helpers
+            // (`to_document`/`to_vec`), not a public `Serializer` type, so
+            // unlike the other arms this one can't be routed through
+            // serde_path_to_error for a dotted failure path.
+            let wrapped = wrap_for_table(value);
+            let document = bson::to_document(&wrapped)?;
+            let mut bytes = Vec::new();
+            document.to_writer(&mut bytes)?;
+            Ok(hex::encode(bytes))
+        }
+        Format::Ini => {
+            let wrapped = wrap_for_table(value);
+            let mut buf = Vec::new();
+            let mut serializer =
+                serde_ini::Serializer::new(serde_ini::Writer::new(&mut buf, Default::default()));
+            serde_path_to_error::serialize(&wrapped, &mut serializer).map_err(path_error)?;
+            Ok(String::from_utf8(buf)?)
+        }
+        Format::Ron => {
+            let mut buf = Vec::new();
+            let mut serializer = ron::Serializer::new(&mut buf, None)?;
+            serde_path_to_error::serialize(value, &mut serializer).map_err(path_error)?;
+            Ok(String::from_utf8(buf)?)
+        }
+        Format::Json5 => {
+            // Like BSON, `json5` only exposes `to_string`, not a public
+            // `Serializer` type, so there's no dotted failure path here.
+            let json5_string = json5::to_string(value)?;
+            Ok(json5_string)
         }
         Format::Toml => {
-            // To serialize Vec<Record> into a meaningful TOML, we usually
-            // put it under a key, as TOML files prefer a top-level table.
-            #[derive(Serialize)]
-            struct TomlWrapper<'a> {
-                records: &'a [Record],
-            }
-            let wrapper = TomlWrapper { records };
-            let toml_string = toml::to_string_pretty(&wrapper)?;
+            // TOML/BSON/INI all require a top-level table; wrap_for_table/
+            // unwrap_from_table let an array-of-records Value (the shape
+            // CSV/JSON use everywhere else here) and a genuine external map
+            // both round-trip instead of only this tool's own output.
+            let wrapped = wrap_for_table(value);
+            let mut toml_string = String::new();
+            let serializer = toml::ser::Serializer::pretty(&mut toml_string);
+            serde_path_to_error::serialize(&wrapped, serializer).map_err(path_error)?;
             Ok(toml_string)
-            // // If you wanted to serialize *just* the array of tables (less common for root):
-            // let toml_string = toml::to_string_pretty(records)?;
+            // // If you wanted to serialize *just* the value (less common for root):
+            // let toml_string = toml::to_string_pretty(value)?;
             // Ok(toml_string)
         }
     }
@@ -156,17 +645,70 @@ fn convert_data(
         "\n---> Converting from {} to {}...",
         input_format, output_format
     );
-    // Step 1: Deserialize input string into our common Rust structure (Vec<Record>)
-    let records = deserialize_from_string(input_string, input_format)?;
-    println!("Deserialized Records: {:?}", records); // Optional: print intermediate struct
+    // Step 1: Deserialize input string into our common intermediary (Value)
+    let value = deserialize_from_string(input_string, input_format)?;
+    println!("Deserialized Value: {:?}", value); // Optional: print intermediate value
 
-    // Step 2: Serialize the Rust structure into the target output string format
-    let output_string = serialize_to_string(&records, output_format)?;
+    // Step 2: Serialize the intermediary into the target output string format
+    let output_string = serialize_to_string(&value, output_format)?;
     Ok(output_string)
 }
 
-// --- Example Usage ---
+/// Like `convert_data`, but infers `input_string`'s format instead of
+/// requiring the caller to know it up front.
+fn convert_auto(input_string: &str, output_format: Format) -> Result<String, ConversionError> {
+    let input_format = Format::detect(input_string).ok_or(ConversionError::UndetectableFormat)?;
+    convert_data(input_string, input_format, output_format)
+}
+
+// --- File-Driven CLI ---
+//
+// `converter <input-file> -o <output-file>` reads the input file, maps both
+// paths' extensions to a `Format` via `Format::from_path`, converts, and
+// writes the result. With no arguments the in-memory demo below runs instead.
+fn run_cli(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut input_path = None;
+    let mut output_path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                output_path = args.next();
+            }
+            other => {
+                if input_path.is_none() {
+                    input_path = Some(other);
+                }
+            }
+        }
+    }
+
+    let input_path = input_path.ok_or("usage: converter <input-file> -o <output-file>")?;
+    let output_path = output_path.ok_or("usage: converter <input-file> -o <output-file>")?;
+
+    let input_format = Format::from_path(Path::new(input_path))?;
+    let output_format = Format::from_path(Path::new(output_path))?;
+
+    let input_string = std::fs::read_to_string(input_path)?;
+    let output_string = convert_data(&input_string, input_format, output_format)?;
+    std::fs::write(output_path, output_string)?;
+
+    println!("Converted {} -> {}", input_path, output_path);
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return run_cli(&args);
+    }
+
+    run_demo()
+}
+
+// --- Example Usage ---
+fn run_demo() -> Result<(), Box<dyn Error>> {
     // --- Sample Data ---
     // Define the initial data as a JSON string
 
@@ -195,29 +737,52 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("Initial JSON:\n{}", initial_json);
 
     // --- Circular Conversion ---
+    // JSON/YAML/TOML all carry the nested `child` array fine since they
+    // share the same Value intermediary; CSV/TSV flatten it into dotted
+    // columns instead, and CSV is demonstrated separately below.
 
     // 1. JSON -> YAML
     let yaml_string = convert_data(initial_json, Format::Json, Format::Yaml)?;
     println!("Converted YAML:\n{}", yaml_string);
 
-    // 2. YAML -> CSV
-    let csv_string = convert_data(&yaml_string, Format::Yaml, Format::Csv)?;
-    println!("Converted CSV:\n{}", csv_string);
-
-    // 3. CSV -> TOML
-    let toml_string = convert_data(&csv_string, Format::Csv, Format::Toml)?;
+    // 2. YAML -> TOML
+    let toml_string = convert_data(&yaml_string, Format::Yaml, Format::Toml)?;
     println!("Converted TOML:\n{}", toml_string);
 
-    // 4. TOML -> JSON
+    // 3. TOML -> JSON
     let final_json_string = convert_data(&toml_string, Format::Toml, Format::Json)?;
     println!("Converted back to JSON:\n{}", final_json_string);
 
     // Optional: Verify the final JSON matches the initial structure (requires deserializing again)
-    let initial_records: Vec<Record> = serde_json::from_str(initial_json)?;
-    let final_records: Vec<Record> = serde_json::from_str(&final_json_string)?;
-    assert_eq!(initial_records, final_records, "Data mismatch after full cycle!");
+    let initial_value: Value = serde_json::from_str(initial_json)?;
+    let final_value: Value = serde_json::from_str(&final_json_string)?;
+    assert_eq!(
+        initial_value, final_value,
+        "Data mismatch after full cycle!"
+    );
     println!("\nâœ… Data matches after full conversion cycle!");
 
+    // CSV is inherently flat, so the nested `child` array is flattened into
+    // dotted columns (`child.0.name`, ...) rather than dropped.
+    let csv_string = convert_data(initial_json, Format::Json, Format::Csv)?;
+    println!("Converted CSV:\n{}", csv_string);
+
+    // ... and re-nested on the way back. This recovers the original shape,
+    // but not always the original types: parse_scalar can't tell a string
+    // that merely looks numeric/boolean ("007", "true") from an actual
+    // number or bool, so JSON -> CSV -> JSON is shape-preserving rather than
+    // guaranteed value-for-value round-trip-safe.
+    let json_from_csv = convert_data(&csv_string, Format::Csv, Format::Json)?;
+    println!("CSV converted back to JSON:\n{}", json_from_csv);
+
+    // --- Auto-Detection ---
+    // Callers that don't know the source format up front can let convert_auto
+    // figure it out instead of calling deserialize_from_string directly.
+    let detected_yaml = convert_auto(&yaml_string, Format::Json)?;
+    println!(
+        "\nAuto-detected YAML and converted back to JSON:\n{}",
+        detected_yaml
+    );
 
     Ok(())
-}
\ No newline at end of file
+}